@@ -3,3 +3,9 @@ pub enum ImeError {
     #[error("Unsupported language format: {0}")]
     Unsupported(String),
 }
+
+impl From<ImeError> for windows::core::Error {
+    fn from(err: ImeError) -> Self {
+        windows::core::Error::new(windows::Win32::Foundation::E_INVALIDARG, err.to_string())
+    }
+}
@@ -0,0 +1,96 @@
+use windows::{
+    Win32::UI::{
+        Input::Ime::{
+            HIMC, ImmGetContext, ImmGetConversionStatus, ImmReleaseContext,
+            ImmSetConversionStatus, IME_CMODE_NATIVE,
+        },
+        WindowsAndMessaging::GetForegroundWindow,
+    },
+    core::{Error, Result},
+};
+
+use crate::lang::LangID;
+use crate::win::{InputMethodManager, switch_input_method};
+
+// 强制切换到中文（本地字符）输入模式
+pub fn force_native(manager: &InputMethodManager) -> Result<()> {
+    set_native_mode(manager, true)
+}
+
+// 强制切换到英文（字母数字）输入模式
+pub fn force_alphanumeric(manager: &InputMethodManager) -> Result<()> {
+    set_native_mode(manager, false)
+}
+
+// 在中文/英文输入模式之间切换
+pub fn toggle(manager: &InputMethodManager) -> Result<()> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return Err(Error::from_win32());
+        }
+
+        let himc = ImmGetContext(hwnd);
+        // 没有输入法上下文（例如只装了英文键盘布局），退回到按当前布局整体切换方向
+        if himc.is_invalid() {
+            let current_hkl = manager.get_current_layout()?;
+            let current_lang_id = (current_hkl.0 as u32) & 0xFFFF;
+            let native = !matches!(LangID::from(current_lang_id), LangID::ZH);
+            manager.save_current_layout(current_hkl)?;
+            return switch_input_method(if native { LangID::ZH } else { LangID::EN });
+        }
+
+        let native = read_native_mode(himc)?;
+        let result = write_native_mode(himc, !native);
+        let _ = ImmReleaseContext(hwnd, himc);
+        result
+    }
+}
+
+fn set_native_mode(manager: &InputMethodManager, native: bool) -> Result<()> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return Err(Error::from_win32());
+        }
+
+        let himc = ImmGetContext(hwnd);
+        // 没有输入法上下文（例如只装了英文键盘布局），退回到按目标语言整体切换布局
+        if himc.is_invalid() {
+            let current_hkl = manager.get_current_layout()?;
+            manager.save_current_layout(current_hkl)?;
+            return switch_input_method(if native { LangID::ZH } else { LangID::EN });
+        }
+
+        let result = write_native_mode(himc, native);
+        let _ = ImmReleaseContext(hwnd, himc);
+        result
+    }
+}
+
+// 读取当前转换模式中的 IME_CMODE_NATIVE 位
+fn read_native_mode(himc: HIMC) -> Result<bool> {
+    unsafe {
+        let mut conversion = 0u32;
+        let mut sentence = 0u32;
+        ImmGetConversionStatus(himc, Some(&mut conversion), Some(&mut sentence))?;
+        Ok(conversion & IME_CMODE_NATIVE.0 != 0)
+    }
+}
+
+// 保留句子模式及其余转换位，仅改写 IME_CMODE_NATIVE 位后写回
+fn write_native_mode(himc: HIMC, native: bool) -> Result<()> {
+    unsafe {
+        let mut conversion = 0u32;
+        let mut sentence = 0u32;
+        ImmGetConversionStatus(himc, Some(&mut conversion), Some(&mut sentence))?;
+
+        let conversion = if native {
+            conversion | IME_CMODE_NATIVE.0
+        } else {
+            conversion & !IME_CMODE_NATIVE.0
+        };
+
+        ImmSetConversionStatus(himc, conversion, sentence)
+    }
+}
@@ -5,26 +5,66 @@ use crate::errors::ImeError;
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 #[repr(u32)]
 pub enum LangID {
-    EN = 0x0409,   // 英文
-    ZH = 0x0804,   // 中文(简体)
-    Zhtw = 0x0404, // 中文(繁体)
-    JA = 0x0411,   // 日语
-    KO = 0x0412,   // 韩语
-    FR = 0x040C,   // 法语
-    DE = 0x0407,   // 德语
-    Other(u32),    // 其他
+    EN = 0x0409,    // 英文(美国)
+    EnGb = 0x0809,  // 英文(英国)
+    ZH = 0x0804,    // 中文(简体)
+    Zhtw = 0x0404,  // 中文(繁体)
+    JA = 0x0411,    // 日语
+    KO = 0x0412,    // 韩语
+    FR = 0x040C,    // 法语
+    FrCa = 0x0C0C,  // 法语(加拿大)
+    DE = 0x0407,    // 德语
+    IT = 0x0410,    // 意大利语
+    ES = 0x0C0A,    // 西班牙语(现代排序)
+    NL = 0x0413,    // 荷兰语
+    PT = 0x0816,    // 葡萄牙语(葡萄牙)
+    PtBr = 0x0416,  // 葡萄牙语(巴西)
+    RU = 0x0419,    // 俄语
+    Other(u32),     // 其他
+}
+
+impl LangID {
+    // 取回该语言对应的数值 LangID，与 From<u32> 互为逆操作
+    pub fn value(self) -> u32 {
+        match self {
+            LangID::EN => 0x0409,
+            LangID::EnGb => 0x0809,
+            LangID::ZH => 0x0804,
+            LangID::Zhtw => 0x0404,
+            LangID::JA => 0x0411,
+            LangID::KO => 0x0412,
+            LangID::FR => 0x040C,
+            LangID::FrCa => 0x0C0C,
+            LangID::DE => 0x0407,
+            LangID::IT => 0x0410,
+            LangID::ES => 0x0C0A,
+            LangID::NL => 0x0413,
+            LangID::PT => 0x0816,
+            LangID::PtBr => 0x0416,
+            LangID::RU => 0x0419,
+            LangID::Other(id) => id,
+        }
+    }
 }
 
 impl From<u32> for LangID {
     fn from(value: u32) -> Self {
         match value {
             0x0409 => LangID::EN,
+            0x0809 => LangID::EnGb,
             0x0804 => LangID::ZH,
             0x0404 => LangID::Zhtw,
             0x0411 => LangID::JA,
             0x0412 => LangID::KO,
             0x040C => LangID::FR,
+            0x0C0C => LangID::FrCa,
             0x0407 => LangID::DE,
+            0x0410 => LangID::IT,
+            0x0C0A | 0x040A => LangID::ES, // 现代排序 / 传统排序均视为西班牙语
+            0x0413 => LangID::NL,
+            0x0816 => LangID::PT,
+            0x0416 => LangID::PtBr,
+            0x0419 => LangID::RU,
             _ => LangID::Other(value),
         }
     }
@@ -43,12 +83,20 @@ impl TryFrom<&str> for LangID {
 
         match s.to_lowercase().as_str() {
             "en" => Ok(LangID::EN),
+            "en-gb" => Ok(LangID::EnGb),
             "zh" | "zh-cn" => Ok(LangID::ZH),
             "zh-tw" => Ok(LangID::Zhtw),
             "ja" | "jp" => Ok(LangID::JA),
             "ko" => Ok(LangID::KO),
             "fr" => Ok(LangID::FR),
+            "fr-ca" => Ok(LangID::FrCa),
             "de" => Ok(LangID::DE),
+            "it" => Ok(LangID::IT),
+            "es" => Ok(LangID::ES),
+            "nl" => Ok(LangID::NL),
+            "pt" => Ok(LangID::PT),
+            "pt-br" => Ok(LangID::PtBr),
+            "ru" => Ok(LangID::RU),
             _ => {
                 if let Ok(num) = s.parse::<u32>() {
                     Ok(num.into())
@@ -63,15 +111,89 @@ impl TryFrom<&str> for LangID {
 impl Display for LangID {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let name = match self {
-            LangID::EN => "英语",
+            LangID::EN => "英语(美国)",
+            LangID::EnGb => "英语(英国)",
             LangID::ZH => "中文(简体)",
             LangID::Zhtw => "中文(繁体)",
             LangID::JA => "日语",
             LangID::KO => "韩语",
             LangID::FR => "法语",
+            LangID::FrCa => "法语(加拿大)",
             LangID::DE => "德语",
+            LangID::IT => "意大利语",
+            LangID::ES => "西班牙语",
+            LangID::NL => "荷兰语",
+            LangID::PT => "葡萄牙语",
+            LangID::PtBr => "葡萄牙语(巴西)",
+            LangID::RU => "俄语",
             LangID::Other(id) => &format!("自定义: {id}"),
         };
         write!(f, "{}", name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMED_VARIANTS: &[LangID] = &[
+        LangID::EN,
+        LangID::EnGb,
+        LangID::ZH,
+        LangID::Zhtw,
+        LangID::JA,
+        LangID::KO,
+        LangID::FR,
+        LangID::FrCa,
+        LangID::DE,
+        LangID::IT,
+        LangID::ES,
+        LangID::NL,
+        LangID::PT,
+        LangID::PtBr,
+        LangID::RU,
+    ];
+
+    #[test]
+    fn value_round_trips_through_from_u32() {
+        for &variant in NAMED_VARIANTS {
+            assert_eq!(LangID::from(variant.value()), variant);
+        }
+    }
+
+    #[test]
+    fn legacy_spanish_code_collapses_onto_es() {
+        // 0x040A（传统排序）与 0x0C0A（现代排序，ES 的规范值）均应识别为西班牙语
+        assert_eq!(LangID::from(0x040A), LangID::ES);
+        assert_eq!(LangID::from(0x0C0A), LangID::ES);
+    }
+
+    #[test]
+    fn unknown_code_round_trips_as_other() {
+        let custom = LangID::from(0x0C09); // 英语(澳大利亚)，未单独建模
+        assert_eq!(LangID::from(custom.value()), custom);
+    }
+
+    #[test]
+    fn try_from_str_accepts_ietf_aliases() {
+        assert_eq!(LangID::try_from("en-gb").unwrap(), LangID::EnGb);
+        assert_eq!(LangID::try_from("fr-ca").unwrap(), LangID::FrCa);
+        assert_eq!(LangID::try_from("pt-br").unwrap(), LangID::PtBr);
+        assert_eq!(LangID::try_from("it").unwrap(), LangID::IT);
+        assert_eq!(LangID::try_from("es").unwrap(), LangID::ES);
+        assert_eq!(LangID::try_from("nl").unwrap(), LangID::NL);
+        assert_eq!(LangID::try_from("pt").unwrap(), LangID::PT);
+        assert_eq!(LangID::try_from("ru").unwrap(), LangID::RU);
+    }
+
+    #[test]
+    fn try_from_str_accepts_hex_and_decimal() {
+        assert_eq!(LangID::try_from("0x0410").unwrap(), LangID::IT);
+        assert_eq!(LangID::try_from("1041").unwrap(), LangID::JA);
+    }
+
+    #[test]
+    fn try_from_str_rejects_unknown_tokens() {
+        assert!(LangID::try_from("xx-yy").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use windows::{
+    Win32::{
+        Foundation::E_FAIL,
+        UI::Input::KeyboardAndMouse::{
+            INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, HKL,
+            SendInput, VIRTUAL_KEY, VK_CONTROL, VK_MENU, VK_SHIFT, VkKeyScanExW,
+        },
+    },
+    core::{Error, Result},
+};
+
+use crate::win::InputMethodManager;
+
+// 在当前激活的键盘布局下，将字符串作为键盘输入发送给前景窗口
+pub fn send_text(manager: &InputMethodManager, text: &str) -> Result<()> {
+    let hkl = manager.get_current_layout()?;
+    let inputs = build_inputs(text, hkl);
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err(Error::new(E_FAIL, "发送输入事件失败"));
+    }
+
+    Ok(())
+}
+
+// 为字符串中的每个字符构造一批 SendInput 事件
+fn build_inputs(text: &str, hkl: HKL) -> Vec<INPUT> {
+    let mut inputs = Vec::new();
+
+    for code_unit in text.encode_utf16() {
+        unsafe {
+            let scan = VkKeyScanExW(code_unit, hkl);
+            // -1 表示当前布局无法映射该字符，退回 Unicode 输入
+            if scan == -1 {
+                push_unicode_pair(&mut inputs, code_unit);
+                continue;
+            }
+
+            let vk = VIRTUAL_KEY(scan as u16 & 0xFF);
+            let modifiers = modifiers_for_shift_state((scan as u16 >> 8) as u8);
+
+            for &modifier in &modifiers {
+                push_key(&mut inputs, modifier, false);
+            }
+            push_key(&mut inputs, vk, false);
+            push_key(&mut inputs, vk, true);
+            for &modifier in modifiers.iter().rev() {
+                push_key(&mut inputs, modifier, true);
+            }
+        }
+    }
+
+    inputs
+}
+
+// VkKeyScanExW 高字节的 bit 0/1/2 分别表示需要 Shift/Ctrl/Alt
+fn modifiers_for_shift_state(shift_state: u8) -> Vec<VIRTUAL_KEY> {
+    let mut modifiers = Vec::new();
+    if shift_state & 0x01 != 0 {
+        modifiers.push(VK_SHIFT);
+    }
+    if shift_state & 0x02 != 0 {
+        modifiers.push(VK_CONTROL);
+    }
+    if shift_state & 0x04 != 0 {
+        modifiers.push(VK_MENU);
+    }
+    modifiers
+}
+
+fn push_key(inputs: &mut Vec<INPUT>, vk: VIRTUAL_KEY, key_up: bool) {
+    inputs.push(INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: 0,
+                dwFlags: if key_up { KEYEVENTF_KEYUP } else { Default::default() },
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    });
+}
+
+fn push_unicode_pair(inputs: &mut Vec<INPUT>, code_unit: u16) {
+    for flags in [KEYEVENTF_UNICODE, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP] {
+        inputs.push(INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: VIRTUAL_KEY(0),
+                    wScan: code_unit,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        });
+    }
+}
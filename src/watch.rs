@@ -0,0 +1,99 @@
+use std::{thread, time::Duration};
+
+use windows::{
+    Win32::{
+        Foundation::CloseHandle,
+        System::Threading::{
+            OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+            QueryFullProcessImageNameW,
+        },
+        UI::{
+            Input::KeyboardAndMouse::{GetKeyboardLayout, HKL},
+            WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId},
+        },
+    },
+    core::{PWSTR, Result},
+};
+
+use crate::win::{switch_to_layout, InputMethodManager};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// 持续监听前景窗口切换：手动改变布局时记住该应用的偏好，切回已知应用时自动恢复
+pub fn watch(manager: &InputMethodManager) -> Result<()> {
+    println!("开始监听前景窗口，按 Ctrl+C 退出...");
+
+    let mut last_app_key: Option<String> = None;
+    let mut last_hkl: Option<HKL> = None;
+
+    loop {
+        if let Err(err) = poll_once(manager, &mut last_app_key, &mut last_hkl) {
+            eprintln!("监听时发生错误: {err}");
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn poll_once(
+    manager: &InputMethodManager,
+    last_app_key: &mut Option<String>,
+    last_hkl: &mut Option<HKL>,
+) -> Result<()> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_invalid() {
+        return Ok(());
+    }
+
+    let mut process_id = 0u32;
+    let thread_id = unsafe { GetWindowThreadProcessId(hwnd, Some(&mut process_id)) };
+    if thread_id == 0 {
+        return Ok(());
+    }
+
+    let app_key = match app_key_for_process(process_id) {
+        Ok(key) => key,
+        Err(_) => return Ok(()), // 权限不足等情况下放弃这一轮
+    };
+    let current_hkl = unsafe { GetKeyboardLayout(thread_id) };
+
+    match (last_app_key.as_deref(), *last_hkl) {
+        // 仍在同一个应用中，但用户手动改变了输入法：记住新的偏好
+        (Some(prev_key), Some(prev_hkl)) if prev_key == app_key && prev_hkl != current_hkl => {
+            manager.remember_app_layout(&app_key, current_hkl)?;
+        }
+        // 切换到了另一个应用：如果有记忆的偏好且与当前不同，恢复它
+        (Some(prev_key), _) if prev_key != app_key => {
+            if let Some(remembered) = manager.layout_for_app(&app_key)? {
+                if remembered != current_hkl {
+                    switch_to_layout(remembered)?;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    *last_app_key = Some(app_key);
+    *last_hkl = Some(current_hkl);
+
+    Ok(())
+}
+
+// 通过进程 ID 反查可执行文件完整路径，作为应用的记忆键
+fn app_key_for_process(process_id: u32) -> Result<String> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id)?;
+
+        let mut buf = [0u16; 512];
+        let mut len = buf.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(buf.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(handle);
+        result?;
+
+        Ok(String::from_utf16_lossy(&buf[..len as usize]))
+    }
+}
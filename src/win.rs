@@ -1,21 +1,32 @@
-use std::{env, fs, path::PathBuf};
+use std::{collections::HashMap, env, fs, path::PathBuf};
 
 use windows::{
     Win32::{
         Foundation::{E_FAIL, E_INVALIDARG, LPARAM, WPARAM},
+        System::Registry::{
+            RegCloseKey, RegEnumValueW, RegOpenKeyExW, RegQueryValueExW, HKEY,
+            HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_READ, REG_SZ, REG_VALUE_TYPE,
+        },
         UI::{
-            Input::KeyboardAndMouse::{GetKeyboardLayout, GetKeyboardLayoutList, HKL},
+            Input::KeyboardAndMouse::{
+                GetKeyboardLayout, GetKeyboardLayoutList, HKL, KLF_ACTIVATE, LoadKeyboardLayoutW,
+                UnloadKeyboardLayout,
+            },
+            Shell::SHLoadIndirectString,
             WindowsAndMessaging::{
                 GetForegroundWindow, GetWindowThreadProcessId, PostMessageW,
                 WM_INPUTLANGCHANGEREQUEST,
             },
         },
     },
-    core::{Error, Result},
+    core::{Error, PCWSTR, PWSTR, Result},
 };
 
 use crate::lang::LangID;
 
+// load_state/save_state 中用于保存“上一次输入法”的保留键，不能与应用可执行文件路径冲突
+const LAST_LAYOUT_KEY: &str = "__last__";
+
 // 输入法状态管理器
 pub struct InputMethodManager {
     config_path: PathBuf,
@@ -33,22 +44,60 @@ impl InputMethodManager {
         Ok(Self { config_path })
     }
 
-    // 保存当前输入法状态
+    // 保存当前输入法状态（作为 --toggle 使用的“上一次”记录）
     pub fn save_current_layout(&self, hkl: HKL) -> Result<()> {
-        let data = format!("{:X}", hkl.0 as u64);
-        fs::write(&self.config_path, data)
-            .map_err(|e| Error::new(E_FAIL, format!("保存状态失败: {}", e)))
+        let mut state = self.load_state()?;
+        state.insert(LAST_LAYOUT_KEY.to_string(), hkl);
+        self.save_state(&state)
     }
 
     // 加载保存的输入法状态
     fn load_saved_layout(&self) -> Result<HKL> {
-        let data = fs::read_to_string(&self.config_path)
-            .map_err(|e| Error::new(E_FAIL, format!("加载状态失败: {}", e)))?;
+        self.load_state()?
+            .get(LAST_LAYOUT_KEY)
+            .copied()
+            .ok_or_else(|| Error::new(E_FAIL, "没有保存的输入法状态"))
+    }
+
+    // 记录某个应用（以可执行文件路径为键）当前偏好使用的输入法
+    pub fn remember_app_layout(&self, app_key: &str, hkl: HKL) -> Result<()> {
+        let mut state = self.load_state()?;
+        state.insert(app_key.to_string(), hkl);
+        self.save_state(&state)
+    }
+
+    // 查询某个应用此前记录的偏好输入法
+    pub fn layout_for_app(&self, app_key: &str) -> Result<Option<HKL>> {
+        Ok(self.load_state()?.get(app_key).copied())
+    }
+
+    // 读取持久化的键值表，文件不存在时视为空表
+    fn load_state(&self) -> Result<HashMap<String, HKL>> {
+        let data = match fs::read_to_string(&self.config_path) {
+            Ok(data) => data,
+            Err(_) => return Ok(HashMap::new()),
+        };
+
+        Ok(data
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .filter_map(|(key, value)| {
+                let hkl_value = u64::from_str_radix(value, 16).ok()?;
+                Some((key.to_string(), HKL(hkl_value as *mut std::ffi::c_void)))
+            })
+            .collect())
+    }
 
-        let hkl_value = u64::from_str_radix(&data, 16)
-            .map_err(|e| Error::new(E_INVALIDARG, format!("无效状态: {}", e)))?;
+    // 以 "键=16进制HKL" 每行一条的形式写回键值表
+    fn save_state(&self, state: &HashMap<String, HKL>) -> Result<()> {
+        let data = state
+            .iter()
+            .map(|(key, hkl)| format!("{key}={:X}", hkl.0 as u64))
+            .collect::<Vec<_>>()
+            .join("\n");
 
-        Ok(HKL(hkl_value as *mut std::ffi::c_void))
+        fs::write(&self.config_path, data)
+            .map_err(|e| Error::new(E_FAIL, format!("保存状态失败: {}", e)))
     }
 
     // 获取当前输入法
@@ -69,10 +118,229 @@ impl InputMethodManager {
             }
         }
     }
+
+    // 从注册表解析每个已加载键盘布局对应的可读名称
+    //
+    // KLID 直接从每个 HKL 自身的值推导（而非按 `Preload` 顺序位置对应，
+    // 因为 GetKeyboardLayoutList 的顺序与 Preload 的相对关系并无文档保证，
+    // 一旦有布局被临时加载或列表重排就会对错号），再经 `Substitutes` 解析覆盖后，
+    // 到 `Keyboard Layouts\<KLID>` 下读取 `Layout Text`（或间接引用的 `Layout Display Name`）。
+    pub fn resolve_layout_names(&self) -> Result<Vec<(HKL, String)>> {
+        unsafe {
+            let layout_count = GetKeyboardLayoutList(None);
+            if layout_count == 0 {
+                return Err(Error::from_win32());
+            }
+
+            let mut layouts = vec![Default::default(); layout_count as usize];
+            let actual_count = GetKeyboardLayoutList(Some(&mut layouts));
+            if actual_count != layout_count {
+                return Err(Error::new(E_INVALIDARG, "获取键盘布局失败"));
+            }
+
+            let substitutes: HashMap<String, String> =
+                enumerate_reg_values(HKEY_CURRENT_USER, r"Keyboard Layout\Substitutes")
+                    .into_iter()
+                    .collect();
+
+            Ok(layouts
+                .into_iter()
+                .map(|hkl| {
+                    let lang_id = (hkl.0 as u32) & 0xFFFF;
+                    let klid = klid_for_hkl(hkl);
+                    let klid = substitutes.get(&klid).cloned().unwrap_or(klid);
+                    let name = layout_text_for_klid(&klid)
+                        .unwrap_or_else(|| LangID::from(lang_id).to_string());
+                    (hkl, name)
+                })
+                .collect())
+        }
+    }
+
+    // 按布局显示名称（忽略大小写的子串匹配）切换输入法
+    pub fn switch_to_layout_by_name(&self, name: &str) -> Result<()> {
+        let needle = name.to_lowercase();
+        let (hkl, _) = self
+            .resolve_layout_names()?
+            .into_iter()
+            .find(|(_, layout_name)| layout_name.to_lowercase().contains(&needle))
+            .ok_or_else(|| Error::new(E_FAIL, format!("未找到名为「{name}」的输入法")))?;
+
+        switch_to_layout(hkl)
+    }
+
+    // 卸载一个此前临时加载的键盘布局
+    pub fn unload_layout(&self, hkl: HKL) -> Result<()> {
+        unsafe { UnloadKeyboardLayout(hkl) }
+    }
+
+    // 按语言查找当前已加载的键盘布局并卸载，用于清理 load_layout 临时激活的布局
+    pub fn unload_layout_for_lang(&self, lang_id: LangID) -> Result<()> {
+        unsafe {
+            let layout_count = GetKeyboardLayoutList(None);
+            if layout_count == 0 {
+                return Err(Error::from_win32());
+            }
+
+            let mut layouts = vec![Default::default(); layout_count as usize];
+            let actual_count = GetKeyboardLayoutList(Some(&mut layouts));
+            if actual_count != layout_count {
+                return Err(Error::new(E_INVALIDARG, "获取键盘布局失败"));
+            }
+
+            let hkl = layouts
+                .into_iter()
+                .find(|hkl| {
+                    let current_lang_id = (hkl.0 as u32) & 0xFFFF;
+                    lang_id == current_lang_id.into()
+                })
+                .ok_or_else(|| Error::new(E_FAIL, format!("未找到{lang_id}输入法")))?;
+
+            self.unload_layout(hkl)
+        }
+    }
+}
+
+// 直接从 HKL 的高字推导其 KLID，不依赖 Preload 中的顺序。
+// 高字为 0xFxxx 的变体布局（如 Dvorak）的 KLID 需按 `Layout Id` 反查注册表，这里不做反查，
+// 得到的字符串不对应任何布局键，其名称随之退回到语言名。
+fn klid_for_hkl(hkl: HKL) -> String {
+    let raw = hkl.0 as u32;
+    let high = (raw >> 16) & 0xFFFF;
+
+    if high & 0xF000 == 0xE000 {
+        // IME 的 HKL 本身即为其 KLID，例如 0xE0200804 -> "E0200804"
+        format!("{:08X}", raw)
+    } else {
+        format!("{:08X}", high)
+    }
+}
+
+// 读取 HKEY_LOCAL_MACHINE\...\Keyboard Layouts\<KLID> 下的布局文本
+fn layout_text_for_klid(klid: &str) -> Option<String> {
+    let subkey = format!(r"System\CurrentControlSet\Control\Keyboard Layouts\{klid}");
+
+    if let Some(indirect) = read_reg_string(HKEY_LOCAL_MACHINE, &subkey, "Layout Display Name") {
+        return Some(resolve_indirect_string(&indirect));
+    }
+
+    read_reg_string(HKEY_LOCAL_MACHINE, &subkey, "Layout Text")
+}
+
+// 解析形如 "@dll,-id" 的间接字符串资源，失败时原样返回
+fn resolve_indirect_string(raw: &str) -> String {
+    if !raw.starts_with('@') {
+        return raw.to_string();
+    }
+
+    unsafe {
+        let src: Vec<u16> = raw.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut out = vec![0u16; 512];
+        if SHLoadIndirectString(PCWSTR(src.as_ptr()), &mut out, None).is_ok() {
+            let len = out.iter().position(|&c| c == 0).unwrap_or(out.len());
+            return String::from_utf16_lossy(&out[..len]);
+        }
+    }
+
+    raw.to_string()
+}
+
+// 读取指定注册表键下单个 REG_SZ 值
+fn read_reg_string(hkey: HKEY, subkey: &str, value: &str) -> Option<String> {
+    unsafe {
+        let subkey_w: Vec<u16> = subkey.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut opened = HKEY::default();
+        if RegOpenKeyExW(hkey, PCWSTR(subkey_w.as_ptr()), Some(0), KEY_READ, &mut opened).is_err()
+        {
+            return None;
+        }
+
+        let value_w: Vec<u16> = value.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut buf_size: u32 = 0;
+        let mut value_type = REG_VALUE_TYPE::default();
+        let sized = RegQueryValueExW(
+            opened,
+            PCWSTR(value_w.as_ptr()),
+            None,
+            Some(&mut value_type),
+            None,
+            Some(&mut buf_size),
+        );
+
+        if sized.is_err() || value_type != REG_SZ || buf_size == 0 {
+            let _ = RegCloseKey(opened);
+            return None;
+        }
+
+        let mut buf = vec![0u16; buf_size as usize / 2 + 1];
+        let mut actual_size = buf_size;
+        let result = RegQueryValueExW(
+            opened,
+            PCWSTR(value_w.as_ptr()),
+            None,
+            None,
+            Some(buf.as_mut_ptr() as *mut u8),
+            Some(&mut actual_size),
+        );
+        let _ = RegCloseKey(opened);
+        result.ok()?;
+
+        let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        Some(String::from_utf16_lossy(&buf[..len]))
+    }
+}
+
+// 枚举指定注册表键下所有 REG_SZ 值，返回 (值名, 值内容)
+fn enumerate_reg_values(hkey: HKEY, subkey: &str) -> Vec<(String, String)> {
+    unsafe {
+        let subkey_w: Vec<u16> = subkey.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut opened = HKEY::default();
+        if RegOpenKeyExW(hkey, PCWSTR(subkey_w.as_ptr()), Some(0), KEY_READ, &mut opened).is_err()
+        {
+            return Vec::new();
+        }
+
+        let mut results = Vec::new();
+        let mut index = 0u32;
+        loop {
+            let mut name_buf = [0u16; 256];
+            let mut name_len = name_buf.len() as u32;
+            let mut value_buf = [0u16; 256];
+            let mut value_len = (value_buf.len() * 2) as u32;
+            let mut value_type = REG_VALUE_TYPE::default();
+
+            let status = RegEnumValueW(
+                opened,
+                index,
+                PWSTR(name_buf.as_mut_ptr()),
+                &mut name_len,
+                None,
+                Some(&mut value_type),
+                Some(value_buf.as_mut_ptr() as *mut u8),
+                Some(&mut value_len),
+            );
+
+            if status.is_err() {
+                break;
+            }
+
+            if value_type == REG_SZ {
+                let name = String::from_utf16_lossy(&name_buf[..name_len as usize]);
+                let value_str_len = (value_len as usize / 2).saturating_sub(1);
+                let value = String::from_utf16_lossy(&value_buf[..value_str_len]);
+                results.push((name, value));
+            }
+
+            index += 1;
+        }
+
+        let _ = RegCloseKey(opened);
+        results
+    }
 }
 
 // 切换输入法核心函数
-fn switch_to_layout(hkl: HKL) -> Result<()> {
+pub(crate) fn switch_to_layout(hkl: HKL) -> Result<()> {
     unsafe {
         // 获取前景窗口
         let hwnd = GetForegroundWindow();
@@ -92,6 +360,22 @@ fn switch_to_layout(hkl: HKL) -> Result<()> {
     }
 }
 
+// 按语言加载并激活对应的键盘布局，适用于尚未出现在 GetKeyboardLayoutList 中的情形
+// （例如刚登录、或是很少使用的布局）
+fn load_layout(lang_id: LangID) -> Result<HKL> {
+    unsafe {
+        let klid = format!("{:08X}", lang_id.value());
+        let klid_w: Vec<u16> = klid.encode_utf16().chain(std::iter::once(0)).collect();
+
+        let hkl = LoadKeyboardLayoutW(PCWSTR(klid_w.as_ptr()), KLF_ACTIVATE);
+        if hkl.is_invalid() {
+            return Err(Error::new(E_FAIL, format!("加载{lang_id}输入法失败")));
+        }
+
+        Ok(hkl)
+    }
+}
+
 pub fn switch_input_method(lang_id: LangID) -> Result<()> {
     unsafe {
         // 1. 获取键盘布局数量
@@ -107,14 +391,14 @@ pub fn switch_input_method(lang_id: LangID) -> Result<()> {
             return Err(Error::new(E_INVALIDARG, "获取键盘布局失败"));
         }
 
-        // 3. 寻找英文输入法 (0x0409)
-        let english_layout = layouts
-            .iter()
-            .find(|hkl| {
-                let current_lang_id = (hkl.0 as u32) & 0xFFFF;
-                lang_id == current_lang_id.into()
-            })
-            .ok_or_else(|| Error::new(E_FAIL, format!("未找到{lang_id}输入法")))?;
+        // 3. 寻找目标语言对应的输入法，若尚未加载则临时加载并激活
+        let target_layout = match layouts.iter().find(|hkl| {
+            let current_lang_id = (hkl.0 as u32) & 0xFFFF;
+            lang_id == current_lang_id.into()
+        }) {
+            Some(hkl) => *hkl,
+            None => load_layout(lang_id)?,
+        };
 
         // 4. 获取前景窗口
         let hwnd = GetForegroundWindow();
@@ -127,7 +411,7 @@ pub fn switch_input_method(lang_id: LangID) -> Result<()> {
             Some(hwnd), // 包装为 Option<HWND>
             WM_INPUTLANGCHANGEREQUEST,
             WPARAM(0),
-            LPARAM(english_layout.0 as isize),
+            LPARAM(target_layout.0 as isize),
         )?; // 直接使用 ? 操作符处理错误
 
         Ok(())
@@ -150,27 +434,15 @@ pub fn toggle_layout(manager: &InputMethodManager) -> Result<()> {
 }
 
 pub fn print_langs() -> Result<()> {
-    unsafe {
-        // 1. 获取键盘布局数量
-        let layout_count = GetKeyboardLayoutList(None);
-        if layout_count == 0 {
-            return Err(Error::from_win32());
-        }
+    let manager = InputMethodManager::new()?;
+    let layouts = manager.resolve_layout_names()?;
 
-        // 2. 获取所有键盘布局
-        let mut layouts = vec![Default::default(); layout_count as usize];
-        let actual_count = GetKeyboardLayoutList(Some(&mut layouts));
-        if actual_count != layout_count {
-            return Err(Error::new(E_INVALIDARG, "获取键盘布局失败"));
-        }
-
-        println!("系统安装的输入法列表 ({} 个):", layouts.len());
-        for (index, hkl) in layouts.iter().enumerate() {
-            let lang_id = (hkl.0 as u32) & 0xFFFF;
-            let lang: LangID = lang_id.into();
-            println!("  [{}] 0x{:04X} {lang}", index + 1, lang_id);
-        }
-
-        Ok(())
+    println!("系统安装的输入法列表 ({} 个):", layouts.len());
+    for (index, (hkl, name)) in layouts.iter().enumerate() {
+        let lang_id = (hkl.0 as u32) & 0xFFFF;
+        let lang: LangID = lang_id.into();
+        println!("  [{}] 0x{:04X} {lang} - {name}", index + 1, lang_id);
     }
+
+    Ok(())
 }